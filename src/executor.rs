@@ -0,0 +1,17 @@
+use crate::transformers::types::Inputs;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct JobMeta {
+    pub name: String,
+    pub transformer: String,
+    pub params: Value,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct JobMessage {
+    pub job_name: String,
+    pub job_meta: JobMeta,
+    pub inputs: Vec<Inputs>,
+}
@@ -0,0 +1,32 @@
+use pgrx::{GucContext, GucFlags, GucRegistry, GucSetting};
+use std::ffi::CStr;
+
+/// Maximum number of estimated tokens packed into a single embedding request.
+pub static BATCH_SIZE: GucSetting<i32> = GucSetting::<i32>::new(3000);
+
+/// Base URL of a self-hosted Ollama instance, used when a job's transformer
+/// selects the Ollama provider. Unset falls back to `http://localhost:11434`.
+pub static OLLAMA_BASE_URL: GucSetting<Option<&'static CStr>> =
+    GucSetting::<Option<&'static CStr>>::new(None);
+
+pub fn init_guc() {
+    GucRegistry::define_int_guc(
+        "vectorize.batch_size",
+        "Maximum token count per embedding request batch",
+        "Inputs are greedily grouped so that no single embedding request exceeds this many estimated tokens.",
+        &BATCH_SIZE,
+        1,
+        i32::MAX,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        "vectorize.ollama_service_url",
+        "Base URL of a self-hosted Ollama instance",
+        "Used for embedding jobs whose transformer selects the Ollama provider, e.g. 'ollama/all-minilm'.",
+        &OLLAMA_BASE_URL,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+}
@@ -0,0 +1,12 @@
+pub mod executor;
+pub mod guc;
+pub mod transformers;
+pub mod types;
+pub mod workers;
+
+pgrx::pg_module_magic!();
+
+#[pgrx::pg_guard]
+pub extern "C" fn _PG_init() {
+    guc::init_guc();
+}
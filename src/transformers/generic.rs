@@ -0,0 +1,16 @@
+use crate::executor::JobMeta;
+use crate::transformers::openai::EmbeddingRequest;
+use crate::transformers::types::Inputs;
+use anyhow::Result;
+
+pub fn prepare_generic_embedding_request(
+    job_meta: JobMeta,
+    inputs: &[Inputs],
+) -> Result<EmbeddingRequest> {
+    Ok(EmbeddingRequest {
+        url: job_meta.transformer,
+        api_key: None,
+        model: "generic".to_string(),
+        input: inputs.iter().map(|i| i.inputs.clone()).collect(),
+    })
+}
@@ -0,0 +1,30 @@
+use crate::transformers::openai::EmbeddingRequest;
+use crate::transformers::types::{Inputs, PairedEmbeddings};
+use anyhow::Result;
+
+pub async fn openai_embedding_request(request: EmbeddingRequest) -> Result<Vec<Vec<f64>>> {
+    let client = reqwest::Client::new();
+    let mut req = client.post(&request.url).json(&request);
+    if let Some(api_key) = &request.api_key {
+        req = req.bearer_auth(api_key);
+    }
+    let resp = req.send().await?.error_for_status()?;
+    let embeddings: Vec<Vec<f64>> = resp.json().await?;
+    Ok(embeddings)
+}
+
+pub fn merge_input_output(
+    inputs: Vec<Inputs>,
+    embeddings: Vec<Vec<f64>>,
+    pkey_type: &str,
+) -> Vec<PairedEmbeddings> {
+    inputs
+        .into_iter()
+        .zip(embeddings)
+        .map(|(input, embeddings)| PairedEmbeddings {
+            primary_key: input.record_id,
+            pkey_type: pkey_type.to_string(),
+            embeddings,
+        })
+        .collect()
+}
@@ -0,0 +1,6 @@
+pub mod generic;
+pub mod http_handler;
+pub mod ollama;
+pub mod openai;
+pub mod providers;
+pub mod types;
@@ -0,0 +1,58 @@
+use crate::executor::JobMeta;
+use crate::guc::OLLAMA_BASE_URL;
+use crate::transformers::openai::EmbeddingRequest;
+use crate::transformers::types::Inputs;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Ollama's batched `/api/embed` request body: a model name and the list of
+/// prompts to embed.
+#[derive(Clone, Debug, Serialize)]
+struct OllamaEmbedRequest {
+    model: String,
+    input: Vec<String>,
+}
+
+/// Ollama's `/api/embed` response: one embedding per input, in order.
+#[derive(Debug, Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f64>>,
+}
+
+pub fn prepare_ollama_request(job_meta: JobMeta, inputs: &[Inputs]) -> Result<EmbeddingRequest> {
+    let base_url = OLLAMA_BASE_URL
+        .get()
+        .map(|s| s.to_str().expect("invalid vectorize.ollama_service_url").to_string())
+        .unwrap_or_else(|| DEFAULT_OLLAMA_URL.to_string());
+    let model = job_meta
+        .transformer
+        .strip_prefix("ollama/")
+        .unwrap_or(&job_meta.transformer)
+        .to_string();
+    Ok(EmbeddingRequest {
+        url: format!("{base_url}/api/embed"),
+        api_key: None,
+        model,
+        input: inputs.iter().map(|i| i.inputs.clone()).collect(),
+    })
+}
+
+/// Dispatches an embedding request against Ollama's `/api/embed` endpoint,
+/// which takes `{model, input: [...]}` and returns `{embeddings: [[...]]}`
+/// rather than the bare `Vec<Vec<f64>>` the OpenAI-shaped HTTP path expects.
+pub async fn ollama_embedding_request(request: EmbeddingRequest) -> Result<Vec<Vec<f64>>> {
+    let body = OllamaEmbedRequest {
+        model: request.model,
+        input: request.input,
+    };
+    let resp = reqwest::Client::new()
+        .post(&request.url)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+    let parsed: OllamaEmbedResponse = resp.json().await?;
+    Ok(parsed.embeddings)
+}
@@ -0,0 +1,24 @@
+use crate::executor::JobMeta;
+use crate::transformers::types::Inputs;
+use anyhow::Result;
+use serde::Serialize;
+
+pub const LLM_BASE_URL: &str = "https://api.openai.com/v1";
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EmbeddingRequest {
+    pub url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    pub input: Vec<String>,
+}
+
+pub fn prepare_openai_request(job_meta: JobMeta, inputs: &[Inputs]) -> Result<EmbeddingRequest> {
+    let api_key = std::env::var("OPENAI_API_KEY").ok();
+    Ok(EmbeddingRequest {
+        url: format!("{LLM_BASE_URL}/embeddings"),
+        api_key,
+        model: job_meta.transformer,
+        input: inputs.iter().map(|i| i.inputs.clone()).collect(),
+    })
+}
@@ -0,0 +1,90 @@
+use crate::executor::JobMeta;
+use crate::transformers::openai::EmbeddingRequest;
+use crate::transformers::types::Inputs;
+use crate::transformers::{generic, http_handler, ollama, openai};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// A backend capable of turning a batch of `Inputs` into embedding vectors.
+///
+/// Implementations own both how the HTTP request is shaped (`prepare_request`)
+/// and how it's dispatched (`embed`), so adding a new backend never requires
+/// touching `execute_job`.
+#[async_trait]
+pub trait EmbeddingProvider {
+    fn prepare_request(&self, job_meta: JobMeta, inputs: &[Inputs]) -> Result<EmbeddingRequest>;
+    async fn embed(&self, request: EmbeddingRequest) -> Result<Vec<Vec<f64>>>;
+    /// Whether returned vectors should be L2-normalized to unit length so the
+    /// search path can use a cheap dot product instead of cosine similarity.
+    fn normalize(&self) -> bool {
+        false
+    }
+}
+
+pub struct OpenAiProvider;
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiProvider {
+    fn prepare_request(&self, job_meta: JobMeta, inputs: &[Inputs]) -> Result<EmbeddingRequest> {
+        openai::prepare_openai_request(job_meta, inputs)
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> Result<Vec<Vec<f64>>> {
+        http_handler::openai_embedding_request(request).await
+    }
+}
+
+pub struct OllamaProvider;
+
+#[async_trait]
+impl EmbeddingProvider for OllamaProvider {
+    fn prepare_request(&self, job_meta: JobMeta, inputs: &[Inputs]) -> Result<EmbeddingRequest> {
+        ollama::prepare_ollama_request(job_meta, inputs)
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> Result<Vec<Vec<f64>>> {
+        ollama::ollama_embedding_request(request).await
+    }
+
+    fn normalize(&self) -> bool {
+        // Ollama models vary in whether they return unit vectors; normalize
+        // unconditionally so cosine and dot-product search agree.
+        true
+    }
+}
+
+pub struct GenericProvider;
+
+#[async_trait]
+impl EmbeddingProvider for GenericProvider {
+    fn prepare_request(&self, job_meta: JobMeta, inputs: &[Inputs]) -> Result<EmbeddingRequest> {
+        generic::prepare_generic_embedding_request(job_meta, inputs)
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> Result<Vec<Vec<f64>>> {
+        http_handler::openai_embedding_request(request).await
+    }
+}
+
+/// Selects a provider from the job's `transformer` string. `ollama/<model>`
+/// routes to the self-hosted Ollama backend, `text-embedding-*` routes to
+/// OpenAI, and anything else falls back to the generic HTTP path.
+pub fn get_provider(transformer: &str) -> Box<dyn EmbeddingProvider + Send + Sync> {
+    if transformer.starts_with("ollama/") {
+        Box::new(OllamaProvider)
+    } else if transformer.starts_with("text-embedding-") {
+        Box::new(OpenAiProvider)
+    } else {
+        Box::new(GenericProvider)
+    }
+}
+
+/// Scales `vector` to unit length. Returns the input unchanged if its norm is
+/// zero to avoid dividing by zero on an all-zero embedding.
+pub fn normalize_vector(vector: Vec<f64>) -> Vec<f64> {
+    let norm = vector.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm == 0.0 {
+        return vector;
+    }
+    vector.into_iter().map(|v| v / norm).collect()
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// A single row queued up for embedding.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Inputs {
+    pub record_id: String,
+    pub inputs: String,
+    /// Estimated token count for `inputs`, computed at enqueue time so the
+    /// worker can batch requests without re-tokenizing on every read.
+    pub token_estimate: i32,
+}
+
+impl Inputs {
+    pub fn new(record_id: String, inputs: String) -> Self {
+        let bpe = tiktoken_rs::cl100k_base().expect("failed to load cl100k_base tokenizer");
+        let token_estimate = bpe.encode_with_special_tokens(&inputs).len() as i32;
+        Inputs {
+            record_id,
+            inputs,
+            token_estimate,
+        }
+    }
+}
+
+/// An embedding paired back up with the record it was generated from.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PairedEmbeddings {
+    pub primary_key: String,
+    /// Postgres type of `primary_key` (e.g. `uuid`, `text`, `bigint`), so the
+    /// writer can cast it back to its native type instead of assuming integer.
+    pub pkey_type: String,
+    pub embeddings: Vec<f64>,
+}
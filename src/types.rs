@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// How embeddings are written back to the source table.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[allow(non_camel_case_types)]
+pub enum TableMethod {
+    /// Embeddings are written as new columns on the source table.
+    append,
+    /// Embeddings are written to a separate table and joined on read.
+    join,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct JobParams {
+    pub schema: String,
+    pub table: String,
+    pub primary_key: String,
+    pub pkey_type: String,
+    pub table_method: TableMethod,
+}
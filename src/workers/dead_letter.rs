@@ -0,0 +1,108 @@
+use crate::executor::JobMessage;
+use anyhow::Result;
+use pgmq::Message;
+use pgrx::*;
+use sqlx::{Pool, Postgres};
+
+/// Archives `msg`'s payload, read count, and `error` into
+/// `vectorize.failed_jobs` before `run_worker` deletes it off the queue.
+pub async fn archive_failed_job(
+    pool: &Pool<Postgres>,
+    msg: &Message<JobMessage>,
+    error: &str,
+) -> Result<()> {
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS vectorize.failed_jobs (
+            msg_id bigint PRIMARY KEY,
+            message jsonb NOT NULL,
+            read_ct int NOT NULL,
+            error text NOT NULL,
+            failed_at timestamptz NOT NULL DEFAULT (now() at time zone 'utc')
+        )
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "
+        INSERT INTO vectorize.failed_jobs (msg_id, message, read_ct, error)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (msg_id) DO UPDATE
+        SET read_ct = EXCLUDED.read_ct, error = EXCLUDED.error, failed_at = (now() at time zone 'utc')
+        ",
+    )
+    .bind(msg.msg_id)
+    .bind(serde_json::to_value(&msg.message)?)
+    .bind(msg.read_ct)
+    .bind(error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Lists jobs that exhausted their retry budget, most recent first.
+#[pg_extern]
+fn list_failed_jobs() -> TableIterator<
+    'static,
+    (
+        name!(msg_id, i64),
+        name!(read_ct, i32),
+        name!(error, String),
+        name!(failed_at, AnyNumeric),
+    ),
+> {
+    let mut rows = Vec::new();
+    Spi::connect(|client| {
+        let tup_table = client
+            .select(
+                "SELECT msg_id, read_ct, error, extract(epoch from failed_at) as failed_at
+                 FROM vectorize.failed_jobs ORDER BY failed_at DESC",
+                None,
+                None,
+            )
+            .expect("failed to query vectorize.failed_jobs");
+        for row in tup_table {
+            let msg_id: i64 = row["msg_id"].value().unwrap().unwrap();
+            let read_ct: i32 = row["read_ct"].value().unwrap().unwrap();
+            let error: String = row["error"].value().unwrap().unwrap();
+            let failed_at: AnyNumeric = row["failed_at"].value().unwrap().unwrap();
+            rows.push((msg_id, read_ct, error, failed_at));
+        }
+    });
+    TableIterator::new(rows)
+}
+
+/// Re-enqueues a dead-lettered job onto `queue_name` for another attempt and
+/// removes it from `vectorize.failed_jobs`.
+#[pg_extern]
+fn requeue_failed_job(msg_id: i64, queue_name: &str) {
+    Spi::connect_mut(|client| {
+        let sent = client
+            .update(
+                "SELECT pgmq.send(queue_name => $1, msg => message)
+                 FROM vectorize.failed_jobs WHERE msg_id = $2",
+                None,
+                &[queue_name.into(), msg_id.into()],
+            )
+            .expect("failed to requeue dead-lettered job");
+
+        // The SELECT...FROM failed_jobs only produces a row (and sends to
+        // the queue) when msg_id actually matched. Only delete the archived
+        // row if something was actually sent, so requeuing an unknown
+        // msg_id is a no-op rather than a silent "success".
+        if sent.is_empty() {
+            warning!("pg-vectorize: no dead-lettered job found for msg_id={msg_id}");
+            return;
+        }
+
+        client
+            .update(
+                "DELETE FROM vectorize.failed_jobs WHERE msg_id = $1",
+                None,
+                &[msg_id.into()],
+            )
+            .expect("failed to remove dead-lettered job");
+    });
+}
@@ -0,0 +1,165 @@
+use anyhow::Result;
+use pgmq::PGMQueueExt;
+use pgrx::*;
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How often the heartbeat extends a running job's visibility timeout and
+/// refreshes `last_heartbeat_at`.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+/// How long each extension keeps the message invisible to other workers.
+const VISIBILITY_EXTENSION_SECS: i32 = 180;
+/// A `vectorize.job_progress` row whose `last_heartbeat_at` is older than
+/// this belongs to a worker that crashed (or gave up) mid-job without
+/// clearing its row.
+const STALE_AFTER_SECS: i64 = 600;
+/// Upper bound on how many times a single job's visibility timeout can be
+/// extended. A job that hits this is hung rather than merely slow: it is
+/// marked 'stuck' and its message is released immediately instead of being
+/// kept invisible indefinitely.
+const MAX_HEARTBEAT_EXTENSIONS: u32 = 120;
+
+async fn ensure_table(pool: &Pool<Postgres>) -> Result<()> {
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS vectorize.job_progress (
+            msg_id bigint PRIMARY KEY,
+            queue_name text NOT NULL,
+            started_at timestamptz NOT NULL DEFAULT (now() at time zone 'utc'),
+            last_heartbeat_at timestamptz NOT NULL DEFAULT (now() at time zone 'utc'),
+            status text NOT NULL DEFAULT 'running'
+        )
+        ",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn mark_started(pool: &Pool<Postgres>, queue_name: &str, msg_id: i64) -> Result<()> {
+    ensure_table(pool).await?;
+    sqlx::query(
+        "
+        INSERT INTO vectorize.job_progress (msg_id, queue_name, status)
+        VALUES ($1, $2, 'running')
+        ON CONFLICT (msg_id) DO UPDATE
+        SET queue_name = EXCLUDED.queue_name,
+            started_at = (now() at time zone 'utc'),
+            last_heartbeat_at = (now() at time zone 'utc'),
+            status = 'running'
+        ",
+    )
+    .bind(msg_id)
+    .bind(queue_name)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn mark_finished(pool: &Pool<Postgres>, msg_id: i64) -> Result<()> {
+    sqlx::query("DELETE FROM vectorize.job_progress WHERE msg_id = $1")
+        .bind(msg_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn touch_heartbeat(pool: &Pool<Postgres>, msg_id: i64) -> Result<()> {
+    sqlx::query(
+        "UPDATE vectorize.job_progress SET last_heartbeat_at = (now() at time zone 'utc') WHERE msg_id = $1",
+    )
+    .bind(msg_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn mark_stuck(pool: &Pool<Postgres>, msg_id: i64) -> Result<()> {
+    sqlx::query("UPDATE vectorize.job_progress SET status = 'stuck' WHERE msg_id = $1")
+        .bind(msg_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Spawns a background task that periodically extends `msg_id`'s visibility
+/// timeout and refreshes `last_heartbeat_at` for as long as the caller's job
+/// is in flight. Abort the returned handle once the job completes normally.
+///
+/// If the job is still running after `MAX_HEARTBEAT_EXTENSIONS` ticks, it's
+/// treated as hung rather than slow: the task marks it 'stuck' and releases
+/// its visibility timeout immediately so another worker can pick it up,
+/// instead of extending forever.
+pub fn spawn_heartbeat(
+    pool: Pool<Postgres>,
+    queue: PGMQueueExt,
+    queue_name: String,
+    msg_id: i64,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        for _ in 0..MAX_HEARTBEAT_EXTENSIONS {
+            tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+            if let Err(e) = queue
+                .set_vt::<serde_json::Value>(&queue_name, msg_id, VISIBILITY_EXTENSION_SECS)
+                .await
+            {
+                warning!("pg-vectorize: failed to extend visibility timeout for msg {msg_id}: {e}");
+            }
+            if let Err(e) = touch_heartbeat(&pool, msg_id).await {
+                warning!("pg-vectorize: failed to record heartbeat for msg {msg_id}: {e}");
+            }
+        }
+
+        warning!("pg-vectorize: msg {msg_id} exceeded {MAX_HEARTBEAT_EXTENSIONS} heartbeats, marking stuck");
+        if let Err(e) = mark_stuck(&pool, msg_id).await {
+            warning!("pg-vectorize: failed to mark msg {msg_id} stuck: {e}");
+        }
+        if let Err(e) = queue.set_vt::<serde_json::Value>(&queue_name, msg_id, 0).await {
+            warning!("pg-vectorize: failed to release stuck msg {msg_id}: {e}");
+        }
+    })
+}
+
+/// A `vectorize.job_progress` row whose `last_heartbeat_at` hasn't moved in
+/// `STALE_AFTER_SECS` means the worker that owned it is gone (crashed, or
+/// already released it as 'stuck'), so its message is made immediately
+/// visible again rather than waiting out its original visibility timeout.
+async fn reclaim_stale_jobs(pool: &Pool<Postgres>, queue: &PGMQueueExt) -> Result<()> {
+    ensure_table(pool).await?;
+    let stale: Vec<(i64, String)> = sqlx::query_as(
+        "
+        SELECT msg_id, queue_name FROM vectorize.job_progress
+        WHERE status IN ('running', 'stuck')
+        AND last_heartbeat_at < (now() at time zone 'utc') - ($1 || ' seconds')::interval
+        ",
+    )
+    .bind(STALE_AFTER_SECS)
+    .fetch_all(pool)
+    .await?;
+
+    for (msg_id, queue_name) in stale {
+        info!("pg-vectorize: reclaiming stale job msg_id={msg_id} on queue {queue_name}");
+        if let Err(e) = queue.set_vt::<serde_json::Value>(&queue_name, msg_id, 0).await {
+            warning!("pg-vectorize: failed to reclaim stale job {msg_id}: {e}");
+            continue;
+        }
+        mark_finished(pool, msg_id).await?;
+    }
+    Ok(())
+}
+
+/// Guards `reclaim_stale_jobs` so it succeeds at most once per worker process,
+/// even though `run_worker` calls this on every poll; a scan that returns an
+/// error leaves the guard unset and is tried again on the next call. `run_worker` handles a
+/// single message per invocation and is driven in a loop by its caller, so
+/// without this gate the stale-job scan (and `ensure_table`'s DDL) would run
+/// on every message instead of once at startup.
+static RECLAIM_ON_STARTUP: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+pub async fn reclaim_stale_jobs_on_startup(pool: &Pool<Postgres>, queue: &PGMQueueExt) -> Result<()> {
+    RECLAIM_ON_STARTUP
+        .get_or_try_init(|| reclaim_stale_jobs(pool, queue))
+        .await?;
+    Ok(())
+}
@@ -1,7 +1,14 @@
+pub mod dead_letter;
+pub mod heartbeat;
 pub mod pg_bgw;
 
 use crate::executor::JobMessage;
-use crate::transformers::{generic, http_handler, openai, types::PairedEmbeddings};
+use crate::guc::BATCH_SIZE;
+use crate::transformers::{
+    http_handler,
+    providers::{get_provider, normalize_vector},
+    types::{Inputs, PairedEmbeddings},
+};
 use crate::types;
 use anyhow::Result;
 use pgmq::{Message, PGMQueueExt};
@@ -13,6 +20,12 @@ pub async fn run_worker(
     conn: &Pool<Postgres>,
     queue_name: &str,
 ) -> Result<Option<()>> {
+    // Release visibility on any job_progress rows with no recent heartbeat.
+    // Gated to run once per worker process — see reclaim_stale_jobs_on_startup.
+    if let Err(e) = heartbeat::reclaim_stale_jobs_on_startup(conn, &queue).await {
+        warning!("pg-vectorize: failed to reclaim stale jobs: {e}");
+    }
+
     let msg: Message<JobMessage> = match queue.read::<JobMessage>(queue_name, 180_i32).await {
         Ok(Some(msg)) => msg,
         Ok(None) => {
@@ -31,11 +44,34 @@ pub async fn run_worker(
         "pg-vectorize: received message for job: {:?}",
         msg.message.job_name
     );
-    let job_success = execute_job(conn.clone(), msg).await;
-    let delete_it = if job_success.is_ok() {
-        true
-    } else {
-        read_ct > 2
+
+    if let Err(e) = heartbeat::mark_started(conn, queue_name, msg_id).await {
+        warning!("pg-vectorize: failed to record job start for msg {msg_id}: {e}");
+    }
+    let heartbeat_handle = heartbeat::spawn_heartbeat(
+        conn.clone(),
+        queue.clone(),
+        queue_name.to_string(),
+        msg_id,
+    );
+
+    let job_success = execute_job(conn.clone(), msg.clone()).await;
+
+    heartbeat_handle.abort();
+    if let Err(e) = heartbeat::mark_finished(conn, msg_id).await {
+        warning!("pg-vectorize: failed to clear job progress for msg {msg_id}: {e}");
+    }
+
+    let delete_it = match &job_success {
+        Ok(_) => true,
+        Err(e) if read_ct > 2 => {
+            if let Err(archive_err) = dead_letter::archive_failed_job(conn, &msg, &e.to_string()).await
+            {
+                warning!("pg-vectorize: failed to archive dead-lettered job {msg_id}: {archive_err}");
+            }
+            true
+        }
+        Err(_) => false,
     };
 
     // delete message from queue
@@ -108,6 +144,49 @@ fn build_upsert_query(
 
 use serde_json::to_string;
 
+// returns query and bindings for a single batched UPDATE ... FROM (VALUES ...)
+// statement, mirroring the shape of build_upsert_query above. Each row casts
+// its primary key with its PairedEmbeddings::pkey_type, so a job can target
+// an integer, uuid, text, or other key type (pkey_type is uniform across a
+// single job's batch — a VALUES list can't mix column types).
+fn build_append_query(
+    schema: &str,
+    table: &str,
+    project: &str,
+    pkey: &str,
+    embeddings: Vec<PairedEmbeddings>,
+) -> (String, Vec<(String, String)>) {
+    let mut query = format!(
+        "
+        UPDATE {schema}.{table} t
+        SET
+            {project}_embeddings = v.embeddings::vector,
+            {project}_updated_at = (NOW() at time zone 'utc')
+        FROM (VALUES"
+    );
+    let mut bindings: Vec<(String, String)> = Vec::new();
+
+    for (index, embed) in embeddings.into_iter().enumerate() {
+        if index > 0 {
+            query.push(',');
+        }
+        let pkey_type = &embed.pkey_type;
+        query.push_str(&format!(
+            " (${}::{pkey_type}, ${}::vector)",
+            2 * index + 1,
+            2 * index + 2
+        ));
+
+        let embedding = to_string(&embed.embeddings).expect("failed to serialize embedding");
+        bindings.push((embed.primary_key, embedding));
+    }
+
+    query.push_str(&format!(") AS v(pkey, embeddings) WHERE t.{pkey} = v.pkey"));
+    (query, bindings)
+}
+
+// Updates every row in a single transaction so a mid-batch failure rolls back
+// the whole batch instead of leaving the table half-updated.
 async fn update_append_table(
     pool: &Pool<Postgres>,
     embeddings: Vec<PairedEmbeddings>,
@@ -115,48 +194,103 @@ async fn update_append_table(
     table: &str,
     project: &str,
     pkey: &str,
-    pkey_type: &str,
 ) -> anyhow::Result<()> {
-    for embed in embeddings {
-        // Serialize the Vec<f64> to a JSON string
-        let embedding = to_string(&embed.embeddings).expect("failed to serialize embedding");
+    if embeddings.is_empty() {
+        return Ok(());
+    }
 
-        // TODO: pkey might not always be integer type
-        let update_query = format!(
-            "
-            UPDATE {schema}.{table}
-            SET 
-                {project}_embeddings = $1::vector,
-                {project}_updated_at = (NOW() at time zone 'utc')
-            WHERE {pkey} = $2::{pkey_type}
-        "
-        );
-        // Prepare and execute the update statement for this pair within the transaction
-        sqlx::query(&update_query)
-            .bind(embedding)
-            .bind(embed.primary_key)
-            .execute(pool)
-            .await?;
+    let (query, bindings) = build_append_query(schema, table, project, pkey, embeddings);
+    let mut q = sqlx::query(&query);
+    for (pkey_val, embedding) in bindings {
+        q = q.bind(pkey_val).bind(embedding);
     }
+
+    let mut tx = pool.begin().await?;
+    q.execute(&mut *tx).await?;
+    tx.commit().await?;
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedding(primary_key: &str, pkey_type: &str) -> PairedEmbeddings {
+        PairedEmbeddings {
+            primary_key: primary_key.to_string(),
+            pkey_type: pkey_type.to_string(),
+            embeddings: vec![0.1, 0.2, 0.3],
+        }
+    }
+
+    #[test]
+    fn build_append_query_casts_uuid_primary_key() {
+        let embeddings = vec![embedding(
+            "6f5a6e2e-8b2b-4a24-9e0a-3a6a7f0e8b9e",
+            "uuid",
+        )];
+        let (query, bindings) = build_append_query("vectorize", "docs", "proj", "id", embeddings);
+
+        assert!(query.contains("$1::uuid"));
+        assert_eq!(bindings[0].0, "6f5a6e2e-8b2b-4a24-9e0a-3a6a7f0e8b9e");
+    }
+
+    #[test]
+    fn build_append_query_casts_text_primary_key() {
+        let embeddings = vec![embedding("some-slug", "text")];
+        let (query, bindings) = build_append_query("vectorize", "docs", "proj", "slug", embeddings);
+
+        assert!(query.contains("$1::text"));
+        assert_eq!(bindings[0].0, "some-slug");
+    }
+}
+
+// Greedily packs `data` into groups whose summed `token_estimate` stays
+// within `batch_size`, starting a new group whenever the next input would
+// push the running total over the limit. A single input larger than
+// `batch_size` still gets its own (oversized) batch rather than being
+// dropped.
+fn create_batches(data: Vec<Inputs>, batch_size: i32) -> Vec<Vec<Inputs>> {
+    let mut batches: Vec<Vec<Inputs>> = Vec::new();
+    let mut current_batch: Vec<Inputs> = Vec::new();
+    let mut current_token_count: i32 = 0;
+
+    for input in data {
+        if !current_batch.is_empty() && current_token_count + input.token_estimate > batch_size {
+            batches.push(current_batch);
+            current_batch = Vec::new();
+            current_token_count = 0;
+        }
+        current_token_count += input.token_estimate;
+        current_batch.push(input);
+    }
+    if !current_batch.is_empty() {
+        batches.push(current_batch);
+    }
+    batches
+}
+
 async fn execute_job(dbclient: Pool<Postgres>, msg: Message<JobMessage>) -> Result<()> {
     let job_meta = msg.message.job_meta;
     let job_params: types::JobParams = serde_json::from_value(job_meta.params.clone())?;
 
-    let embedding_request = match job_meta.transformer.as_ref() {
-        "text-embedding-ada-002" => {
-            info!("pg-vectorize: OpenAI transformer");
-            openai::prepare_openai_request(job_meta.clone(), &msg.message.inputs)
-        }
-        _ => generic::prepare_generic_embedding_request(job_meta.clone(), &msg.message.inputs),
-    }?;
+    let provider = get_provider(&job_meta.transformer);
+    let batches = create_batches(msg.message.inputs, BATCH_SIZE.get());
+    let mut paired_embeddings: Vec<PairedEmbeddings> = Vec::new();
 
-    let embeddings = http_handler::openai_embedding_request(embedding_request).await?;
-    // TODO: validate returned embeddings order is same as the input order
-    let paired_embeddings: Vec<PairedEmbeddings> =
-        http_handler::merge_input_output(msg.message.inputs, embeddings);
+    for batch in batches {
+        let embedding_request = provider.prepare_request(job_meta.clone(), &batch)?;
+        let mut embeddings = provider.embed(embedding_request).await?;
+        if provider.normalize() {
+            embeddings = embeddings.into_iter().map(normalize_vector).collect();
+        }
+        // TODO: validate returned embeddings order is same as the input order
+        paired_embeddings.extend(http_handler::merge_input_output(
+            batch,
+            embeddings,
+            &job_params.pkey_type,
+        ));
+    }
 
     // write embeddings to result table
     match job_params.clone().table_method {
@@ -168,7 +302,6 @@ async fn execute_job(dbclient: Pool<Postgres>, msg: Message<JobMessage>) -> Resu
                 &job_params.table,
                 &job_meta.clone().name,
                 &job_params.primary_key,
-                &job_params.pkey_type,
             )
             .await?;
         }
@@ -0,0 +1,23 @@
+use pgrx::bgworkers::*;
+use std::time::Duration;
+
+pub fn init_bgworker() {
+    BackgroundWorkerBuilder::new("pg-vectorize background worker")
+        .set_function("background_worker_main")
+        .set_library("vectorize")
+        .enable_spi_access()
+        .load();
+}
+
+#[pgrx::pg_guard]
+#[no_mangle]
+pub extern "C" fn background_worker_main(_arg: pgrx::pg_sys::Datum) {
+    BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGHUP | SignalWakeFlags::SIGTERM);
+    BackgroundWorker::connect_worker_to_spi(Some("pg_vectorize"), None);
+
+    while BackgroundWorker::wait_latch(Some(Duration::from_secs(1))) {
+        if BackgroundWorker::sighup_received() {
+            // reload config
+        }
+    }
+}